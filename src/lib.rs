@@ -1,6 +1,6 @@
 //! Slog Kickstarter. Easily sets up slog for structured logging.
 //!
-//! - enables JSON logging if `RUST_LOG_JSON=1` (i.e. set `RUST_LOG_JSON=1` for your deployment, or put `ENV RUST_LOG_JSON=1` into your Dockerfile)
+//! - selects the output format via `RUST_LOG_FORMAT=json|compact|full|plain|syslog` (the legacy `RUST_LOG_JSON=1` still enforces JSON)
 //! - inits and configures stdlogger, so crates using `info!()` from the (default) [log-crate](https://crates.io/crates/log) can log messages
 //! - allows to enable debugging for given modules (typically your own modules)
 //! - sets default loglevel 'Info'
@@ -18,14 +18,151 @@
 
 use chrono::prelude::*;
 use chrono::Local;
-use slog::Record;
-use slog::{o, Drain, FilterLevel, Fuse, Logger};
+use slog::{o, Drain, FilterLevel, Key, Level, Logger, Never, Record, Serializer, KV};
 use slog::{FnValue, PushFnValue};
-use slog_async::Async;
+use slog::{OwnedKVList, Result as SlogResult};
 use slog_envlogger::LogBuilder as EnvLogBuilder;
 use slog_json::Json;
-use slog_term::{CompactFormat, TermDecorator};
+use slog_term::{CompactFormat, FullFormat, PlainDecorator, TermDecorator};
 use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// the fully-assembled root drain, type-erased so the async and synchronous wrappers share a type
+type RootDrain = Box<
+    dyn Drain<Ok = (), Err = Never>
+        + Send
+        + Sync
+        + std::panic::RefUnwindSafe
+        + std::panic::UnwindSafe,
+>;
+
+/// the output format for log records
+///
+/// selectable via `RUST_LOG_FORMAT=json|compact|full|plain|syslog`, or programmatically via
+/// [`SlogKickstarter::with_format`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// structured JSON, one object per line (for log shippers)
+    Json,
+    /// compact, human-friendly terminal output (the default)
+    Compact,
+    /// verbose terminal output with one key/value block per record
+    Full,
+    /// plain (non-coloured) text, suitable for non-TTY/file contexts
+    Plain,
+    /// syslog-compatible lines (`<severity>timestamp service msg key=value…`)
+    Syslog,
+}
+
+impl OutputFormat {
+    /// parse a format from its `RUST_LOG_FORMAT` spelling, case-insensitively
+    fn from_env_value(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "compact" => Some(Self::Compact),
+            "full" => Some(Self::Full),
+            "plain" => Some(Self::Plain),
+            "syslog" => Some(Self::Syslog),
+            _ => None,
+        }
+    }
+}
+
+/// map a slog [`Level`] to the matching syslog severity (RFC 5424)
+fn syslog_severity(level: Level) -> u8 {
+    match level {
+        Level::Critical => 2,
+        Level::Error => 3,
+        Level::Warning => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// collects a record's key/value pairs into `key=value` order for syslog output
+#[derive(Default)]
+struct KeyValueSerializer {
+    pairs: Vec<(String, String)>,
+}
+
+impl Serializer for KeyValueSerializer {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments) -> SlogResult {
+        self.pairs.push((key.to_string(), val.to_string()));
+        Ok(())
+    }
+}
+
+/// a minimal syslog/RFC5424-style drain: `<severity>timestamp service msg key=value…`
+///
+/// the structured key/values (`service`, `module`, `version`, …) are appended as `key=value`
+/// pairs; `service` is additionally surfaced as the service-name token.
+struct SyslogFormat<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> SyslogFormat<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl<W: Write> Drain for SyslogFormat<W> {
+    type Ok = ();
+    type Err = io::Error;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let to_io = |e: slog::Error| io::Error::other(e.to_string());
+
+        // gather the structured key/values carried by the logger and the record
+        let mut serializer = KeyValueSerializer::default();
+        values.serialize(record, &mut serializer).map_err(to_io)?;
+        record.kv().serialize(record, &mut serializer).map_err(to_io)?;
+
+        let severity = syslog_severity(record.level());
+        let timestamp = Local::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let service = serializer
+            .pairs
+            .iter()
+            .find(|(k, _)| k == "service")
+            .map(|(_, v)| v.as_str())
+            .unwrap_or("-");
+
+        let kvs = serializer
+            .pairs
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(writer, "<{severity}>{timestamp} {service} {} {kvs}", record.msg())
+    }
+}
+
+/// which standard stream log records are written to
+///
+/// selectable via [`SlogKickstarter::with_output_stream`]. When left unset the stream defaults
+/// per format: JSON goes to stdout (for log shippers), human/terminal output to stderr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stream {
+    /// standard output
+    Stdout,
+    /// standard error
+    Stderr,
+}
+
+/// a directory + filename prefix for optional on-disk log output
+struct FileOutput {
+    dir: PathBuf,
+    prefix: String,
+}
 
 /// the actual slog builder
 pub struct SlogKickstarter {
@@ -33,23 +170,37 @@ pub struct SlogKickstarter {
     debug_modules: Vec<&'static str>,
     service_name: String,
     init_std_log: bool,
-    use_json_logging: bool,
+    format: OutputFormat,
+    file_output: Option<FileOutput>,
+    synchronous: bool,
+    output_stream: Option<Stream>,
 }
 
 impl SlogKickstarter {
     #[must_use]
     /// initialize the log-builder with a name for your service
     pub fn new<S: Into<String>>(service_name: S) -> Self {
-        let use_json_logging = env::var("RUST_LOG_JSON")
-            .map(|v| v == "1")
-            .unwrap_or_default();
+        // `RUST_LOG_FORMAT` supersedes the legacy `RUST_LOG_JSON=1`
+        let format = env::var("RUST_LOG_FORMAT")
+            .ok()
+            .and_then(|v| OutputFormat::from_env_value(&v))
+            .or_else(|| {
+                env::var("RUST_LOG_JSON")
+                    .map(|v| v == "1")
+                    .unwrap_or_default()
+                    .then_some(OutputFormat::Json)
+            })
+            .unwrap_or(OutputFormat::Compact);
 
         Self {
             default_filter_level: FilterLevel::Info,
             debug_modules: vec![],
             service_name: service_name.into(),
             init_std_log: true,
-            use_json_logging,
+            format,
+            file_output: None,
+            synchronous: false,
+            output_stream: None,
         }
     }
 
@@ -66,20 +217,65 @@ impl SlogKickstarter {
         self
     }
 
+    /// select the output format
+    ///
+    /// this should typically be set via `RUST_LOG_FORMAT=json|compact|full|plain|syslog`
+    pub fn with_format(&mut self, format: OutputFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
     /// enforce JSON logging
     ///
-    /// this should typically be set via `RUST_LOG_JSON=1`
+    /// this should typically be set via `RUST_LOG_JSON=1` (or `RUST_LOG_FORMAT=json`)
     pub fn with_json_logging(&mut self) -> &mut Self {
-        self.use_json_logging = true;
+        self.format = OutputFormat::Json;
         self
     }
 
     /// enforce **no** JSON logging
     ///
-    /// this should typically be set via `RUST_LOG_JSON=0`, or just leaving out `RUST_LOG_JSON`,
-    /// as this is the default
+    /// this falls back to the compact terminal format; prefer [`SlogKickstarter::with_format`]
+    /// to pick a specific non-JSON format
     pub fn without_json_logging(&mut self) -> &mut Self {
-        self.use_json_logging = false;
+        self.format = OutputFormat::Compact;
+        self
+    }
+
+    /// write logs to a timestamped file instead of the configured stream
+    ///
+    /// records are written to `{dir}/{prefix}-{YYYYMMDDHHMM}.log`; the directory is created with
+    /// [`std::fs::create_dir_all`] on [`SlogKickstarter::init`]. Terminal formats force plain
+    /// decoration in this mode so no ANSI escapes end up in the file.
+    pub fn with_file_output<P: Into<PathBuf>>(
+        &mut self,
+        dir: P,
+        prefix: impl Into<String>,
+    ) -> &mut Self {
+        self.file_output = Some(FileOutput {
+            dir: dir.into(),
+            prefix: prefix.into(),
+        });
+        self
+    }
+
+    /// write to a specific standard stream instead of the per-format default
+    ///
+    /// without this, JSON is written to stdout (for log shippers) and terminal/human output to
+    /// stderr, so application logs can be separated from program output in pipelines. Has no
+    /// effect when [`SlogKickstarter::with_file_output`] is set.
+    pub fn with_output_stream(&mut self, stream: Stream) -> &mut Self {
+        self.output_stream = Some(stream);
+        self
+    }
+
+    /// write every record synchronously instead of through the async background worker
+    ///
+    /// the async drain buffers records on a worker thread, so a log emitted right before a
+    /// panic/abort can be lost when the worker never flushes. With this flag every record is
+    /// written inline (behind a [`Mutex`]) before control returns — at the cost of throughput.
+    pub fn with_synchronous_logging(&mut self) -> &mut Self {
+        self.synchronous = true;
         self
     }
 
@@ -92,11 +288,13 @@ impl SlogKickstarter {
     /// initialize the logger based on the builder
     #[must_use]
     pub fn init(&self) -> Logger {
-        // output in json-format iff RUST_LOG_JSON=1
-        let drain = if self.use_json_logging {
-            self.setup_json_logging()
-        } else {
-            self.setup_term_logging()
+        // pick the drain based on the configured output format
+        let drain = match self.format {
+            OutputFormat::Json => self.setup_json_logging(),
+            OutputFormat::Syslog => self.setup_syslog_logging(),
+            OutputFormat::Compact | OutputFormat::Full | OutputFormat::Plain => {
+                self.setup_term_logging(self.format)
+            }
         };
 
         if self.init_std_log {
@@ -112,8 +310,64 @@ impl SlogKickstarter {
         )
     }
 
-    fn setup_json_logging(&self) -> Fuse<Async> {
-        let drain = Json::new(std::io::stdout())
+    /// apply the env-logger filtering (default level, per-module debug, `RUST_LOG`) to a fused
+    /// formatter drain and wrap the result in either the async worker or a synchronous mutex
+    fn finish_drain<D>(&self, drain: D) -> RootDrain
+    where
+        D: Drain<Ok = (), Err = Never> + Send + 'static,
+    {
+        // builder with given default loglevel as default for all modules
+        let builder = EnvLogBuilder::new(drain).filter(None, self.default_filter_level);
+
+        // set debug-exceptions for specific modules
+        let builder = self.debug_modules.iter().fold(builder, |b, &module_name| {
+            b.filter(Some(module_name), FilterLevel::Debug)
+        });
+
+        let drain = builder
+            // override with RUST_LOG (if given)
+            .parse(env::var("RUST_LOG").unwrap_or_default().as_str())
+            .build()
+            .fuse();
+
+        if self.synchronous {
+            // write inline behind a mutex so records are flushed before control returns
+            Box::new(Mutex::new(drain).fuse())
+        } else {
+            Box::new(slog_async::Async::new(drain).build().fuse())
+        }
+    }
+
+    /// the stream to write to: explicit override, else the per-format default
+    /// (JSON → stdout, everything else → stderr)
+    fn resolved_stream(&self) -> Stream {
+        self.output_stream.unwrap_or(match self.format {
+            OutputFormat::Json => Stream::Stdout,
+            _ => Stream::Stderr,
+        })
+    }
+
+    /// open the configured log file (creating its directory), or fall back to the resolved stream
+    fn writer(&self) -> Box<dyn io::Write + Send> {
+        match &self.file_output {
+            Some(file) => Box::new(self.open_file(file)),
+            None => match self.resolved_stream() {
+                Stream::Stdout => Box::new(io::stdout()),
+                Stream::Stderr => Box::new(io::stderr()),
+            },
+        }
+    }
+
+    /// create the log directory and open `{dir}/{prefix}-{YYYYMMDDHHMM}.log`
+    fn open_file(&self, file: &FileOutput) -> fs::File {
+        fs::create_dir_all(&file.dir).expect("could not create log directory");
+        let timestamp = Local::now().format("%Y%m%d%H%M");
+        let path = file.dir.join(format!("{}-{}.log", file.prefix, timestamp));
+        fs::File::create(&path).expect("could not create log file")
+    }
+
+    fn setup_json_logging(&self) -> RootDrain {
+        let drain = Json::new(self.writer())
             .add_key_value(o!(
             "@timestamp" => PushFnValue(move |_ : &Record, ser| {
                 ser.emit(Local::now().to_rfc3339_opts(SecondsFormat::Secs, true))
@@ -128,41 +382,59 @@ impl SlogKickstarter {
             .build()
             .fuse();
 
-        let builder = EnvLogBuilder::new(drain)
-            // set default log-level 'info'…
-            .filter(None, self.default_filter_level);
-
-        let builder = self.debug_modules.iter().fold(builder, |b, &module_name| {
-            b.filter(Some(module_name), FilterLevel::Debug)
-        });
-
-        let drain = builder
-            //but override with RUST_LOG (if given)
-            .parse(env::var("RUST_LOG").unwrap_or_default().as_str())
-            .build()
-            .fuse();
-
-        slog_async::Async::new(drain).build().fuse()
+        self.finish_drain(drain)
     }
 
-    fn setup_term_logging(&self) -> Fuse<Async> {
-        let decorator = TermDecorator::new().build();
-        let drain = CompactFormat::new(decorator).build().fuse();
+    fn setup_syslog_logging(&self) -> RootDrain {
+        let drain = SyslogFormat::new(self.writer()).fuse();
 
-        // builder with given default loglevel as default for all modules
-        let builder = EnvLogBuilder::new(drain).filter(None, self.default_filter_level);
-
-        // set debug-exceptions for specific modules
-        let builder = self.debug_modules.iter().fold(builder, |b, &module_name| {
-            b.filter(Some(module_name), FilterLevel::Debug)
-        });
+        self.finish_drain(drain)
+    }
 
-        let drain = builder
-            // override with RUST_LOG (if given)
-            .parse(env::var("RUST_LOG").unwrap_or_default().as_str())
-            .build()
-            .fuse();
+    fn setup_term_logging(&self, format: OutputFormat) -> RootDrain {
+        // box the formatter so the compact/full/plain variants share a single drain type
+        let drain: Box<dyn Drain<Ok = (), Err = Never> + Send> = match &self.file_output {
+            // writing to a file: force plain decoration so no ANSI escapes pollute the file
+            Some(file) => {
+                let decorator = PlainDecorator::new(self.open_file(file));
+                match format {
+                    OutputFormat::Compact => {
+                        Box::new(CompactFormat::new(decorator).build().fuse())
+                    }
+                    // in file mode the decorator is already plain, so `Plain` and `Full` both
+                    // resolve to `FullFormat` over a `PlainDecorator` and produce identical output
+                    OutputFormat::Full | OutputFormat::Plain => {
+                        Box::new(FullFormat::new(decorator).build().fuse())
+                    }
+                    OutputFormat::Json | OutputFormat::Syslog => {
+                        unreachable!("non-terminal format")
+                    }
+                }
+            }
+            None => {
+                // direct the terminal decorator at the resolved stream
+                let decorator = match self.resolved_stream() {
+                    Stream::Stdout => TermDecorator::new().stdout(),
+                    Stream::Stderr => TermDecorator::new().stderr(),
+                };
+                match format {
+                    OutputFormat::Compact => {
+                        Box::new(CompactFormat::new(decorator.build()).build().fuse())
+                    }
+                    OutputFormat::Full => {
+                        Box::new(FullFormat::new(decorator.build()).build().fuse())
+                    }
+                    OutputFormat::Plain => {
+                        Box::new(FullFormat::new(decorator.force_plain().build()).build().fuse())
+                    }
+                    // JSON and syslog are handled by `setup_json_logging` / `setup_syslog_logging`
+                    OutputFormat::Json | OutputFormat::Syslog => {
+                        unreachable!("non-terminal format")
+                    }
+                }
+            }
+        };
 
-        slog_async::Async::new(drain).build().fuse()
+        self.finish_drain(drain)
     }
 }